@@ -22,12 +22,19 @@
 #[macro_use]
 mod macros;
 
+mod strategy;
+
+pub use strategy::Strategy;
+
 #[cfg(unix)]
 mod unix;
 
 #[cfg(unix)]
 use unix::*;
 
+#[cfg(target_os = "macos")]
+mod macos;
+
 #[cfg(windows)]
 mod windows;
 
@@ -35,7 +42,9 @@ mod windows;
 use windows::*;
 
 use std::env;
-use std::path::PathBuf;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 
 
 /// Enumerates the standard location type.
@@ -52,7 +61,7 @@ pub enum LocationType {
     ///
     /// * On Unix systems it's equal to the `$HOME` environment variable.
     /// * On the last Windows operating systems it's equal to the `%HomePath%`
-    /// environment variable.
+    ///   environment variable.
     HomeLocation,
     /// The user's desktop directory.
     DesktopLocation,
@@ -127,12 +136,31 @@ pub enum LocationType {
     AppConfigLocation
 }
 
+/// Restricts [StandardPaths::locate](struct.StandardPaths.html#method.locate) and
+/// [StandardPaths::locate_all](struct.StandardPaths.html#method.locate_all)
+/// results to either existing files or existing directories.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LocateOption {
+    /// Only match entries that exist and are regular files.
+    LocateFile,
+    /// Only match entries that exist and are directories.
+    LocateDirectory
+}
+
 /// Stores application and organization names and provides all the crate methods.
 pub struct StandardPaths {
     /// Application name.
     app_name: String,
     /// organization name.
-    organisation_name: String
+    organisation_name: String,
+    /// Base-directory strategy used to resolve locations.
+    strategy: Strategy
+}
+
+impl Default for StandardPaths {
+    fn default() -> StandardPaths {
+        StandardPaths::new()
+    }
 }
 
 impl StandardPaths {
@@ -141,11 +169,9 @@ impl StandardPaths {
     /// derived from the `CARGO_PKG_NAME` variable.
     pub fn new() -> StandardPaths {
         StandardPaths {
-            app_name: match env::var("CARGO_PKG_NAME") {
-                Ok(name) => name,
-                _ => String::new()
-            },
-            organisation_name: String::new()
+            app_name: env::var("CARGO_PKG_NAME").unwrap_or_default(),
+            organisation_name: String::new(),
+            strategy: Strategy::native()
         }
     }
 
@@ -153,7 +179,24 @@ impl StandardPaths {
     pub fn new_with_names(app: &'static str, organisation: &'static str) -> StandardPaths {
         StandardPaths {
             app_name: app.into(),
-            organisation_name: organisation.into()
+            organisation_name: organisation.into(),
+            strategy: Strategy::native()
+        }
+    }
+
+    /// Constructs a new `StandardPaths` with the provided `app` and `organization`
+    /// names that resolves locations using `strategy` instead of whichever one
+    /// matches the host platform.
+    ///
+    /// # Arguments
+    /// * `app` - application name.
+    /// * `organisation` - organization name.
+    /// * `strategy` - the base-directory strategy to resolve locations with.
+    pub fn new_with_strategy(app: &'static str, organisation: &'static str, strategy: Strategy) -> StandardPaths {
+        StandardPaths {
+            app_name: app.into(),
+            organisation_name: organisation.into(),
+            strategy
         }
     }
 
@@ -163,7 +206,7 @@ impl StandardPaths {
     ///
     /// # Arguments
     /// * `path` - a mutable `PathBuf` to which the app suffix should be appended.
-    fn append_organization_and_app(&self, path: &mut PathBuf) {
+    pub(crate) fn append_organization_and_app(&self, path: &mut PathBuf) {
         if !self.organisation_name.is_empty() {
             path.push(&self.organisation_name);
         }
@@ -182,7 +225,7 @@ impl StandardPaths {
     /// # Arguments
     /// * `location` - location type.
     pub fn writable_location(&self, location: LocationType) -> Option<PathBuf> {
-        self.writable_location_impl(location)
+        strategy_for(self.strategy).writable_location(self, location)
     }
 
     /// Returns all the directories of type `location`.
@@ -197,7 +240,62 @@ impl StandardPaths {
     /// # Arguments
     /// * `location` - location type.
     pub fn standard_locations(&self, location: LocationType) -> Option<Vec<PathBuf>> {
-        self.standard_locations_impl(location)
+        strategy_for(self.strategy).standard_locations(self, location)
+    }
+
+    /// Like [writable_location](struct.StandardPaths.html#method.writable_location),
+    /// but also creates the full directory chain so the returned path is
+    /// ready to use. On Unix, [RuntimeLocation](enum.LocationType.html#variant.RuntimeLocation)
+    /// and the per-app config/cache locations are created with the
+    /// restrictive `0700` mode from the start, rather than relaxed and then
+    /// tightened afterwards.
+    ///
+    /// # Arguments
+    /// * `location` - location type.
+    pub fn writable_location_create(&self, location: LocationType) -> io::Result<PathBuf> {
+        let path = self.writable_location(location.clone()).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "location could not be determined")
+        })?;
+        create_dir_all_restricted(&path, &location)?;
+        Ok(path)
+    }
+
+    /// Returns the first existing file or directory named `file_name` found
+    /// within the [standard locations](struct.StandardPaths.html#method.standard_locations)
+    /// of type `location`, searched in priority order.
+    ///
+    /// Returns [None](https://doc.rust-lang.org/std/option/enum.Option.html#variant.None)
+    /// if no match is found.
+    ///
+    /// # Arguments
+    /// * `location` - location type.
+    /// * `file_name` - path relative to each standard location, e.g. `"app.conf"`.
+    /// * `flag` - whether `file_name` must resolve to a file or to a directory.
+    pub fn locate(&self, location: LocationType, file_name: &str, flag: LocateOption) -> Option<PathBuf> {
+        self.locate_all(location, file_name, flag).map(|mut matches| matches.remove(0))
+    }
+
+    /// Returns every existing file or directory named `file_name` found within
+    /// the [standard locations](struct.StandardPaths.html#method.standard_locations)
+    /// of type `location`, in the same priority order.
+    ///
+    /// Returns [None](https://doc.rust-lang.org/std/option/enum.Option.html#variant.None)
+    /// if no match is found.
+    ///
+    /// # Arguments
+    /// * `location` - location type.
+    /// * `file_name` - path relative to each standard location, e.g. `"app.conf"`.
+    /// * `flag` - whether `file_name` must resolve to a file or to a directory.
+    pub fn locate_all(&self, location: LocationType, file_name: &str, flag: LocateOption) -> Option<Vec<PathBuf>> {
+        let dirs = self.standard_locations(location)?;
+        let matches: Vec<PathBuf> = dirs.into_iter()
+            .map(|dir| dir.join(file_name))
+            .filter(|candidate| match flag {
+                LocateOption::LocateFile => candidate.is_file(),
+                LocateOption::LocateDirectory => candidate.is_dir()
+            })
+            .collect();
+        if matches.is_empty() { None } else { Some(matches) }
     }
 
     /// Returns the absolute file path to the executable with `name` in the system path.
@@ -217,7 +315,7 @@ impl StandardPaths {
     ///
     /// # Arguments
     /// * `name` - the name of the searched executable or an absolute path
-    /// which should be checked to be executable.
+    ///   which should be checked to be executable.
     pub fn find_executable<S>(name: S) -> Option<Vec<PathBuf>>
     where S: Into<String> {
         let paths: Vec<PathBuf> = Vec::new();
@@ -234,10 +332,183 @@ impl StandardPaths {
     ///
     /// # Arguments
     /// * `name` - the name of the searched executable or an absolute path
-    /// which should be checked to be executable.
+    ///   which should be checked to be executable.
     /// * `paths` - the directories where to search for the executable.
     pub fn find_executable_in_paths<S>(name: S, paths: Vec<PathBuf>) -> Option<Vec<PathBuf>>
     where S: Into<String> {
         find_executable_in_paths_impl(name, paths)
     }
 }
+
+/// The directory mode that newly created directories for `location` should
+/// get, restricting to the owning user for locations that are private to
+/// this app: [RuntimeLocation](enum.LocationType.html#variant.RuntimeLocation)
+/// and the per-app config/cache locations. Shared locations (e.g.
+/// `GenericConfigLocation`) get the regular `0777` (subject to umask), since
+/// other apps may already have created them with their own permissions.
+#[cfg(unix)]
+fn directory_mode(location: &LocationType) -> u32 {
+    let needs_restriction = matches!(*location,
+        LocationType::RuntimeLocation | LocationType::ConfigLocation |
+        LocationType::AppConfigLocation | LocationType::AppCacheLocation);
+    if needs_restriction { 0o700 } else { 0o777 }
+}
+
+/// Creates the full directory chain for `path`, with [directory_mode]'s mode
+/// applied only to the final, `location`-specific component and at creation
+/// time rather than relaxed-then-chmod'd, so that component is never briefly
+/// world-readable. Ancestors are created with the regular default mode: a
+/// restrictive mode is only meaningful for the directory private to this
+/// app/location, and `recursive(true)` with `mode` set would otherwise stamp
+/// every ancestor it has to create (e.g. a not-yet-existing shared
+/// `~/.config`) with that same restrictive mode too. A final component that
+/// already exists is chmod'd to `0700` if its location is restricted, and is
+/// otherwise left with the permissions it already has.
+#[cfg(unix)]
+fn create_dir_all_restricted(path: &Path, location: &LocationType) -> io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mode = directory_mode(location);
+    match fs::DirBuilder::new().mode(mode).create(path) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+            if mode == 0o700 {
+                fs::set_permissions(path, fs::Permissions::from_mode(mode))
+            } else {
+                Ok(())
+            }
+        },
+        Err(err) => Err(err)
+    }
+}
+
+/// Non-Unix platforms have no equivalent of the XDG spec's permission
+/// requirements, so this is a plain `create_dir_all`.
+#[cfg(not(unix))]
+fn create_dir_all_restricted(path: &Path, _location: &LocationType) -> io::Result<()> {
+    fs::create_dir_all(path)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Serializes tests that mutate process-wide environment variables, since
+    /// `cargo test` otherwise runs them concurrently on the same process.
+    pub(crate) static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn locate_all_finds_file_in_overridden_xdg_data_home() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let temp_dir = env::temp_dir().join("standard_paths-test-locate_all");
+        fs::create_dir_all(&temp_dir).unwrap();
+        fs::write(temp_dir.join("app.conf"), b"").unwrap();
+
+        let original = env::var_os("XDG_DATA_HOME");
+        unsafe { env::set_var("XDG_DATA_HOME", &temp_dir) };
+
+        let sp = StandardPaths::new_with_strategy("app", "org", Strategy::Xdg);
+        let found = sp.locate_all(LocationType::GenericDataLocation, "app.conf", LocateOption::LocateFile);
+        assert_eq!(found, Some(vec![temp_dir.join("app.conf")]));
+
+        match original {
+            Some(value) => unsafe { env::set_var("XDG_DATA_HOME", value) },
+            None => unsafe { env::remove_var("XDG_DATA_HOME") }
+        }
+        fs::remove_dir_all(&temp_dir).ok();
+    }
+
+    #[test]
+    fn locate_all_returns_none_when_nothing_matches() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let temp_dir = env::temp_dir().join("standard_paths-test-locate_all-missing");
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        let original = env::var_os("XDG_DATA_HOME");
+        unsafe { env::set_var("XDG_DATA_HOME", &temp_dir) };
+
+        let sp = StandardPaths::new_with_strategy("app", "org", Strategy::Xdg);
+        let found = sp.locate_all(LocationType::GenericDataLocation, "missing.conf", LocateOption::LocateFile);
+        assert_eq!(found, None);
+
+        match original {
+            Some(value) => unsafe { env::set_var("XDG_DATA_HOME", value) },
+            None => unsafe { env::remove_var("XDG_DATA_HOME") }
+        }
+        fs::remove_dir_all(&temp_dir).ok();
+    }
+
+    #[test]
+    fn writable_location_create_restricts_app_config_to_owner() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let _guard = ENV_LOCK.lock().unwrap();
+        let temp_dir = env::temp_dir().join("standard_paths-test-writable_location_create-app");
+        fs::remove_dir_all(&temp_dir).ok();
+
+        let original = env::var_os("XDG_CONFIG_HOME");
+        unsafe { env::set_var("XDG_CONFIG_HOME", &temp_dir) };
+
+        let sp = StandardPaths::new_with_strategy("app", "org", Strategy::Xdg);
+        let path = sp.writable_location_create(LocationType::AppConfigLocation).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o700);
+
+        match original {
+            Some(value) => unsafe { env::set_var("XDG_CONFIG_HOME", value) },
+            None => unsafe { env::remove_var("XDG_CONFIG_HOME") }
+        }
+        fs::remove_dir_all(&temp_dir).ok();
+    }
+
+    #[test]
+    fn writable_location_create_leaves_generic_config_unrestricted() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let _guard = ENV_LOCK.lock().unwrap();
+        let temp_dir = env::temp_dir().join("standard_paths-test-writable_location_create-generic");
+        fs::remove_dir_all(&temp_dir).ok();
+
+        let original = env::var_os("XDG_CONFIG_HOME");
+        unsafe { env::set_var("XDG_CONFIG_HOME", &temp_dir) };
+
+        let sp = StandardPaths::new_with_strategy("app", "org", Strategy::Xdg);
+        let path = sp.writable_location_create(LocationType::GenericConfigLocation).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        assert_ne!(mode, 0o700);
+
+        match original {
+            Some(value) => unsafe { env::set_var("XDG_CONFIG_HOME", value) },
+            None => unsafe { env::remove_var("XDG_CONFIG_HOME") }
+        }
+        fs::remove_dir_all(&temp_dir).ok();
+    }
+
+    #[test]
+    fn writable_location_create_does_not_restrict_shared_ancestor() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let _guard = ENV_LOCK.lock().unwrap();
+        // $XDG_CONFIG_HOME itself must not exist yet, so creating the
+        // app-specific leaf also has to create this shared ancestor.
+        let temp_dir = env::temp_dir().join("standard_paths-test-writable_location_create-ancestor");
+        fs::remove_dir_all(&temp_dir).ok();
+
+        let original = env::var_os("XDG_CONFIG_HOME");
+        unsafe { env::set_var("XDG_CONFIG_HOME", &temp_dir) };
+
+        let sp = StandardPaths::new_with_strategy("app", "org", Strategy::Xdg);
+        sp.writable_location_create(LocationType::AppConfigLocation).unwrap();
+        let ancestor_mode = fs::metadata(&temp_dir).unwrap().permissions().mode() & 0o777;
+        assert_ne!(ancestor_mode, 0o700);
+
+        match original {
+            Some(value) => unsafe { env::set_var("XDG_CONFIG_HOME", value) },
+            None => unsafe { env::remove_var("XDG_CONFIG_HOME") }
+        }
+        fs::remove_dir_all(&temp_dir).ok();
+    }
+}
@@ -0,0 +1,63 @@
+use std::path::PathBuf;
+
+use ::LocationType;
+use ::LocationType::*;
+use ::StandardPaths;
+use ::strategy::LocationStrategy;
+use ::unix::AppleStandardStrategy;
+
+/// [`LocationStrategy`](../strategy/trait.LocationStrategy.html) implementation
+/// used natively on macOS.
+///
+/// Per-user paths are identical to the [`unix::AppleStandardStrategy`](../unix/struct.AppleStandardStrategy.html)
+/// other Unix hosts use to emulate the Apple layout, so this delegates to it;
+/// the only addition is that `standard_locations` also includes the
+/// system-wide `/Library/...` counterpart, matching how `QStandardPaths`
+/// behaves when actually running on macOS.
+pub(crate) struct AppleNativeStrategy;
+
+impl LocationStrategy for AppleNativeStrategy {
+
+    #[inline]
+    fn writable_location(&self, paths: &StandardPaths, location: LocationType) -> Option<PathBuf> {
+        AppleStandardStrategy.writable_location(paths, location)
+    }
+
+    #[inline]
+    fn standard_locations(&self, paths: &StandardPaths, location: LocationType) -> Option<Vec<PathBuf>> {
+        let mut dirs = Vec::new();
+        if let Some(path) = self.writable_location(paths, location.clone()) {
+            dirs.push(path);
+        }
+        if let Some(path) = system_library_path(&location, paths) {
+            dirs.push(path);
+        }
+        Some(dirs)
+    }
+}
+
+/// System-wide `/Library/...` counterpart of the per-user location, for the
+/// location types that have one. Returns `None` for locations `/Library` has
+/// no concept of (e.g. the per-user media folders).
+fn system_library_path(location: &LocationType, paths: &StandardPaths) -> Option<PathBuf> {
+    let mut path = PathBuf::from("/Library");
+    match *location {
+        GenericDataLocation | AppDataLocation | AppLocalDataLocation =>
+            path.push("Application Support"),
+        GenericCacheLocation | AppCacheLocation =>
+            path.push("Caches"),
+        GenericConfigLocation | ConfigLocation | AppConfigLocation =>
+            path.push("Preferences"),
+        FontsLocation =>
+            path.push("Fonts"),
+        _ => return None
+    }
+
+    match *location {
+        AppDataLocation | AppLocalDataLocation | AppCacheLocation | AppConfigLocation =>
+            paths.append_organization_and_app(&mut path),
+        _ => {}
+    }
+
+    Some(path)
+}
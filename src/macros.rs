@@ -0,0 +1,24 @@
+//! Small platform-conditional helpers for the platform backends, defined once
+//! per platform so that a caller needs no `cfg` of its own.
+
+/// Builds an `OsString` from a platform-native string buffer, hiding the Unix
+/// (`OsStringExt::from_vec`) vs Windows (`OsStringExt::from_wide`) conversion
+/// behind a single call site. Every element of `$buf` is kept, so the buffer
+/// must not include a null terminator.
+#[cfg(unix)]
+macro_rules! os_string_from_native {
+    ($buf:expr) => {{
+        use std::os::unix::ffi::OsStringExt;
+        ::std::ffi::OsString::from_vec($buf)
+    }};
+}
+
+/// Windows counterpart of the macro above: `$buf` is expected to be a `&[u16]`
+/// of UTF-16 code units rather than raw bytes.
+#[cfg(windows)]
+macro_rules! os_string_from_native {
+    ($buf:expr) => {{
+        use std::os::windows::ffi::OsStringExt;
+        ::std::ffi::OsString::from_wide($buf)
+    }};
+}
@@ -0,0 +1,66 @@
+//! Base-directory strategies used by
+//! [`StandardPaths`](../struct.StandardPaths.html) to turn a
+//! [`LocationType`](../enum.LocationType.html) into concrete paths.
+//!
+//! A `StandardPaths` picks the strategy matching the host platform by
+//! default, but [`StandardPaths::new_with_strategy`](../struct.StandardPaths.html#method.new_with_strategy)
+//! lets callers pin a specific one instead.
+
+use std::path::PathBuf;
+
+use ::LocationType;
+use ::StandardPaths;
+
+/// Selects which family of directory conventions a `StandardPaths` follows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Strategy {
+    /// The XDG Base Directory Specification, as used on Linux and other
+    /// freedesktop.org-compliant Unix systems.
+    Xdg,
+    /// Apple's `~/Library/...` layout, as used on macOS.
+    AppleStandard,
+    /// The Windows Known Folder API (`SHGetKnownFolderPath`).
+    WindowsKnownFolder
+}
+
+impl Strategy {
+    /// Returns the strategy matching the platform this code was compiled for.
+    #[cfg(target_os = "macos")]
+    pub fn native() -> Strategy { Strategy::AppleStandard }
+
+    /// Returns the strategy matching the platform this code was compiled for.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn native() -> Strategy { Strategy::Xdg }
+
+    /// Returns the strategy matching the platform this code was compiled for.
+    #[cfg(windows)]
+    pub fn native() -> Strategy { Strategy::WindowsKnownFolder }
+}
+
+/// Resolves [`LocationType`](../enum.LocationType.html) values into concrete
+/// paths for one particular [`Strategy`](enum.Strategy.html).
+///
+/// Implemented once per strategy variant; `StandardPaths` dispatches to the
+/// implementation matching `self.strategy` chosen at construction time.
+pub trait LocationStrategy {
+    /// Strategy-specific counterpart of `StandardPaths::writable_location`.
+    fn writable_location(&self, paths: &StandardPaths, location: LocationType) -> Option<PathBuf>;
+
+    /// Strategy-specific counterpart of `StandardPaths::standard_locations`.
+    fn standard_locations(&self, paths: &StandardPaths, location: LocationType) -> Option<Vec<PathBuf>>;
+}
+
+/// Placeholder for a [`Strategy`](enum.Strategy.html) with no implementation
+/// on this platform (e.g. `WindowsKnownFolder` on Linux); every lookup
+/// returns `None`.
+pub(crate) struct UnsupportedStrategy;
+
+impl LocationStrategy for UnsupportedStrategy {
+    fn writable_location(&self, _paths: &StandardPaths, _location: LocationType) -> Option<PathBuf> {
+        None
+    }
+
+    fn standard_locations(&self, _paths: &StandardPaths, _location: LocationType) -> Option<Vec<PathBuf>> {
+        None
+    }
+}
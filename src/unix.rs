@@ -0,0 +1,433 @@
+extern crate libc;
+
+use std::env;
+use std::ffi::CStr;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+use ::LocationType;
+use ::LocationType::*;
+use ::StandardPaths;
+use ::Strategy;
+use ::strategy::LocationStrategy;
+
+/// Buffer size (in bytes) used for the `getpwuid_r` lookup when
+/// `sysconf(_SC_GETPW_R_SIZE_MAX)` doesn't report a usable value.
+const FALLBACK_PWBUF_SIZE: usize = 512;
+
+/// Resolves the current user's home directory.
+///
+/// `$HOME` is trusted when it's set to a non-empty value, matching every other
+/// XDG-aware tool. Otherwise the passwd database entry for the effective user
+/// is consulted via the re-entrant `getpwuid_r`, since `std::env::home_dir()`
+/// is deprecated and can't be relied on inside daemons, `sudo`/`su` contexts,
+/// or containers where `$HOME` may be absent or empty.
+pub(crate) fn resolve_home_dir() -> Option<PathBuf> {
+    if let Some(home) = env::var_os("HOME") {
+        if !home.is_empty() {
+            return Some(PathBuf::from(home));
+        }
+    }
+    home_dir_from_passwd()
+}
+
+/// Looks up `pw_dir` for the effective user id via `getpwuid_r`.
+///
+/// Returns `None` if the lookup fails or the passwd entry has no home
+/// directory set.
+fn home_dir_from_passwd() -> Option<PathBuf> {
+    let buf_size = match unsafe { libc::sysconf(libc::_SC_GETPW_R_SIZE_MAX) } {
+        size if size > 0 => size as usize,
+        _ => FALLBACK_PWBUF_SIZE
+    };
+    let mut buf: Vec<libc::c_char> = vec![0; buf_size];
+    let mut passwd: libc::passwd = unsafe { mem::zeroed() };
+    let mut result: *mut libc::passwd = ptr::null_mut();
+
+    let status = unsafe {
+        libc::getpwuid_r(
+            libc::geteuid(),
+            &mut passwd,
+            buf.as_mut_ptr(),
+            buf.len(),
+            &mut result
+        )
+    };
+
+    if status != 0 || result.is_null() || passwd.pw_dir.is_null() {
+        return None;
+    }
+
+    let pw_dir = unsafe { CStr::from_ptr(passwd.pw_dir) };
+    let path = PathBuf::from(os_string_from_native!(pw_dir.to_bytes().to_vec()));
+    if path.as_os_str().is_empty() {
+        None
+    } else {
+        Some(path)
+    }
+}
+
+/// Splits a colon-separated environment variable (like `$PATH` or
+/// `$XDG_DATA_DIRS`) into its component paths, skipping empty entries.
+fn split_paths_env(value: &str) -> Vec<PathBuf> {
+    value.split(':').filter(|s| !s.is_empty()).map(PathBuf::from).collect()
+}
+
+/// Reads an environment variable expected to hold a single absolute path,
+/// falling back to `$HOME/$default_suffix` when it's unset or empty.
+fn xdg_dir_or_default(var: &str, default_suffix: &str) -> Option<PathBuf> {
+    match env::var_os(var) {
+        Some(value) if !value.is_empty() => Some(PathBuf::from(value)),
+        _ => resolve_home_dir().map(|mut home| {
+            home.push(default_suffix);
+            home
+        })
+    }
+}
+
+/// [`LocationStrategy`](../strategy/trait.LocationStrategy.html) implementation
+/// for the XDG Base Directory Specification, as used on Linux and other
+/// freedesktop.org-compliant Unix systems.
+pub(crate) struct XdgStrategy;
+
+impl LocationStrategy for XdgStrategy {
+
+    #[inline]
+    fn writable_location(&self, paths: &StandardPaths, location: LocationType) -> Option<PathBuf> {
+        match location {
+
+            HomeLocation => resolve_home_dir(),
+
+            RuntimeLocation => env::var_os("XDG_RUNTIME_DIR")
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from),
+
+            TempLocation => Some(env::temp_dir()),
+
+            DesktopLocation => resolve_home_dir().map(|mut home| { home.push("Desktop"); home }),
+            DocumentsLocation => resolve_home_dir().map(|mut home| { home.push("Documents"); home }),
+            DownloadLocation => resolve_home_dir().map(|mut home| { home.push("Downloads"); home }),
+            MoviesLocation => resolve_home_dir().map(|mut home| { home.push("Videos"); home }),
+            MusicLocation => resolve_home_dir().map(|mut home| { home.push("Music"); home }),
+            PicturesLocation => resolve_home_dir().map(|mut home| { home.push("Pictures"); home }),
+            ApplicationsLocation => None,
+
+            FontsLocation => {
+                xdg_dir_or_default("XDG_DATA_HOME", ".local/share").map(|mut path| {
+                    path.push("fonts");
+                    path
+                })
+            },
+
+            GenericDataLocation => xdg_dir_or_default("XDG_DATA_HOME", ".local/share"),
+            AppDataLocation | AppLocalDataLocation => {
+                self.writable_location(paths, GenericDataLocation).map(|mut path| {
+                    paths.append_organization_and_app(&mut path);
+                    path
+                })
+            },
+
+            GenericCacheLocation => xdg_dir_or_default("XDG_CACHE_HOME", ".cache"),
+            AppCacheLocation => {
+                self.writable_location(paths, GenericCacheLocation).map(|mut path| {
+                    paths.append_organization_and_app(&mut path);
+                    path
+                })
+            },
+
+            GenericConfigLocation => xdg_dir_or_default("XDG_CONFIG_HOME", ".config"),
+            ConfigLocation | AppConfigLocation => {
+                self.writable_location(paths, GenericConfigLocation).map(|mut path| {
+                    paths.append_organization_and_app(&mut path);
+                    path
+                })
+            }
+        }
+    }
+
+    #[inline]
+    fn standard_locations(&self, paths: &StandardPaths, location: LocationType) -> Option<Vec<PathBuf>> {
+        let mut dirs = Vec::new();
+        if let Some(path) = self.writable_location(paths, location.clone()) {
+            dirs.push(path);
+        }
+
+        let dirs_env = match location {
+            GenericDataLocation | AppDataLocation | AppLocalDataLocation => Some("XDG_DATA_DIRS"),
+            GenericConfigLocation | ConfigLocation | AppConfigLocation => Some("XDG_CONFIG_DIRS"),
+            _ => None
+        };
+
+        if let Some(var) = dirs_env {
+            let is_app_specific = location != GenericDataLocation && location != GenericConfigLocation;
+            if let Some(value) = env::var_os(var) {
+                if let Some(value) = value.to_str() {
+                    for mut dir in split_paths_env(value) {
+                        if is_app_specific {
+                            paths.append_organization_and_app(&mut dir);
+                        }
+                        dirs.push(dir);
+                    }
+                }
+            }
+        }
+
+        Some(dirs)
+    }
+}
+
+/// [`LocationStrategy`](../strategy/trait.LocationStrategy.html) implementation
+/// for Apple's `~/Library/...` layout, as used on macOS.
+///
+/// Available on any Unix host (not just macOS), since it only needs the
+/// `$HOME`/passwd-based home directory resolution shared with
+/// [`XdgStrategy`](struct.XdgStrategy.html).
+pub(crate) struct AppleStandardStrategy;
+
+impl LocationStrategy for AppleStandardStrategy {
+
+    #[inline]
+    fn writable_location(&self, paths: &StandardPaths, location: LocationType) -> Option<PathBuf> {
+        let library = || resolve_home_dir().map(|mut home| { home.push("Library"); home });
+
+        match location {
+
+            HomeLocation => resolve_home_dir(),
+            RuntimeLocation => None,
+            TempLocation => Some(env::temp_dir()),
+
+            DesktopLocation => resolve_home_dir().map(|mut home| { home.push("Desktop"); home }),
+            DocumentsLocation => resolve_home_dir().map(|mut home| { home.push("Documents"); home }),
+            DownloadLocation => resolve_home_dir().map(|mut home| { home.push("Downloads"); home }),
+            MoviesLocation => resolve_home_dir().map(|mut home| { home.push("Movies"); home }),
+            MusicLocation => resolve_home_dir().map(|mut home| { home.push("Music"); home }),
+            PicturesLocation => resolve_home_dir().map(|mut home| { home.push("Pictures"); home }),
+            ApplicationsLocation => Some(PathBuf::from("/Applications")),
+
+            FontsLocation => library().map(|mut path| { path.push("Fonts"); path }),
+
+            GenericDataLocation => library().map(|mut path| { path.push("Application Support"); path }),
+            AppDataLocation | AppLocalDataLocation => {
+                self.writable_location(paths, GenericDataLocation).map(|mut path| {
+                    paths.append_organization_and_app(&mut path);
+                    path
+                })
+            },
+
+            GenericCacheLocation => library().map(|mut path| { path.push("Caches"); path }),
+            AppCacheLocation => {
+                self.writable_location(paths, GenericCacheLocation).map(|mut path| {
+                    paths.append_organization_and_app(&mut path);
+                    path
+                })
+            },
+
+            GenericConfigLocation => library().map(|mut path| { path.push("Preferences"); path }),
+            ConfigLocation | AppConfigLocation => {
+                self.writable_location(paths, GenericConfigLocation).map(|mut path| {
+                    paths.append_organization_and_app(&mut path);
+                    path
+                })
+            }
+        }
+    }
+
+    #[inline]
+    fn standard_locations(&self, paths: &StandardPaths, location: LocationType) -> Option<Vec<PathBuf>> {
+        let mut dirs = Vec::new();
+        if let Some(path) = self.writable_location(paths, location) {
+            dirs.push(path);
+        }
+        Some(dirs)
+    }
+}
+
+/// Looks up the [`LocationStrategy`](../strategy/trait.LocationStrategy.html)
+/// implementation for `kind` on this platform, falling back to
+/// [`UnsupportedStrategy`](../strategy/struct.UnsupportedStrategy.html) for
+/// strategies this platform has no implementation for (e.g.
+/// `WindowsKnownFolder` on Unix).
+///
+/// On macOS, `AppleStandard` resolves to the dedicated
+/// [`macos::AppleNativeStrategy`](../macos/struct.AppleNativeStrategy.html)
+/// rather than this module's [`AppleStandardStrategy`](struct.AppleStandardStrategy.html),
+/// which it builds on for the per-user paths.
+#[cfg(target_os = "macos")]
+#[doc(hidden)]
+pub fn strategy_for(kind: Strategy) -> Box<dyn LocationStrategy> {
+    match kind {
+        Strategy::Xdg => Box::new(XdgStrategy),
+        Strategy::AppleStandard => Box::new(::macos::AppleNativeStrategy),
+        Strategy::WindowsKnownFolder => Box::new(::strategy::UnsupportedStrategy)
+    }
+}
+
+/// Looks up the [`LocationStrategy`](../strategy/trait.LocationStrategy.html)
+/// implementation for `kind` on this platform, falling back to
+/// [`UnsupportedStrategy`](../strategy/struct.UnsupportedStrategy.html) for
+/// strategies this platform has no implementation for (e.g.
+/// `WindowsKnownFolder` on Unix).
+#[cfg(not(target_os = "macos"))]
+#[doc(hidden)]
+pub fn strategy_for(kind: Strategy) -> Box<dyn LocationStrategy> {
+    match kind {
+        Strategy::Xdg => Box::new(XdgStrategy),
+        Strategy::AppleStandard => Box::new(AppleStandardStrategy),
+        Strategy::WindowsKnownFolder => Box::new(::strategy::UnsupportedStrategy)
+    }
+}
+
+#[doc(hidden)]
+pub fn find_executable_in_paths_impl<S>(name: S, mut paths: Vec<PathBuf>) -> Option<Vec<PathBuf>>
+where S: Into<String> {
+    let name = name.into();
+    let candidate = PathBuf::from(&name);
+    if candidate.is_absolute() {
+        return if is_executable_file(&candidate) { Some(vec![candidate]) } else { None };
+    }
+
+    if paths.is_empty() {
+        if let Some(path_var) = env::var_os("PATH") {
+            if let Some(path_var) = path_var.to_str() {
+                paths = split_paths_env(path_var);
+            }
+        }
+    }
+
+    let found: Vec<PathBuf> = paths.into_iter()
+        .map(|dir| dir.join(&name))
+        .filter(|candidate| is_executable_file(candidate))
+        .collect();
+
+    if found.is_empty() { None } else { Some(found) }
+}
+
+/// Returns `true` if `path` points to a regular file with at least one
+/// executable bit set.
+fn is_executable_file(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    match path.metadata() {
+        Ok(meta) => meta.is_file() && meta.permissions().mode() & 0o111 != 0,
+        Err(_) => false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The crate-level lock, so these tests are also serialized with the
+    /// ones in `lib.rs` that mutate the same environment variables.
+    use ::tests::ENV_LOCK;
+
+    #[test]
+    fn resolve_home_dir_trusts_home_when_set() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let original = env::var_os("HOME");
+        unsafe { env::set_var("HOME", "/tmp/fake-home") };
+        assert_eq!(resolve_home_dir(), Some(PathBuf::from("/tmp/fake-home")));
+
+        match original {
+            Some(value) => unsafe { env::set_var("HOME", value) },
+            None => unsafe { env::remove_var("HOME") }
+        }
+    }
+
+    #[test]
+    fn resolve_home_dir_falls_back_to_passwd_when_home_is_empty() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let original = env::var_os("HOME");
+        unsafe { env::set_var("HOME", "") };
+
+        let resolved = resolve_home_dir();
+        assert_eq!(resolved, home_dir_from_passwd());
+
+        match original {
+            Some(value) => unsafe { env::set_var("HOME", value) },
+            None => unsafe { env::remove_var("HOME") }
+        }
+    }
+
+    #[test]
+    fn xdg_dir_or_default_uses_env_var_when_set() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        unsafe { env::set_var("XDG_CONFIG_HOME", "/tmp/fake-config") };
+        assert_eq!(
+            xdg_dir_or_default("XDG_CONFIG_HOME", ".config"),
+            Some(PathBuf::from("/tmp/fake-config"))
+        );
+    }
+
+    #[test]
+    fn xdg_dir_or_default_falls_back_to_home_suffix_when_unset() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        unsafe { env::remove_var("XDG_CACHE_HOME") };
+        unsafe { env::set_var("HOME", "/tmp/fake-home") };
+        assert_eq!(
+            xdg_dir_or_default("XDG_CACHE_HOME", ".cache"),
+            Some(PathBuf::from("/tmp/fake-home/.cache"))
+        );
+    }
+
+    #[test]
+    fn xdg_strategy_standard_locations_splits_and_appends_config_dirs() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let original_home = env::var_os("XDG_CONFIG_HOME");
+        let original_dirs = env::var_os("XDG_CONFIG_DIRS");
+        unsafe { env::set_var("XDG_CONFIG_HOME", "/tmp/fake-config") };
+        unsafe { env::set_var("XDG_CONFIG_DIRS", "/etc/xdg:/etc/xdg2") };
+
+        let sp = StandardPaths::new_with_strategy("app", "org", Strategy::Xdg);
+
+        // App-specific locations get the org/app suffix appended to every
+        // directory found in $XDG_CONFIG_DIRS, on top of $XDG_CONFIG_HOME.
+        assert_eq!(
+            sp.standard_locations(AppConfigLocation),
+            Some(vec![
+                PathBuf::from("/tmp/fake-config/org/app"),
+                PathBuf::from("/etc/xdg/org/app"),
+                PathBuf::from("/etc/xdg2/org/app")
+            ])
+        );
+        // The generic location is shared, so no app suffix is appended.
+        assert_eq!(
+            sp.standard_locations(GenericConfigLocation),
+            Some(vec![
+                PathBuf::from("/tmp/fake-config"),
+                PathBuf::from("/etc/xdg"),
+                PathBuf::from("/etc/xdg2")
+            ])
+        );
+
+        match original_home {
+            Some(value) => unsafe { env::set_var("XDG_CONFIG_HOME", value) },
+            None => unsafe { env::remove_var("XDG_CONFIG_HOME") }
+        }
+        match original_dirs {
+            Some(value) => unsafe { env::set_var("XDG_CONFIG_DIRS", value) },
+            None => unsafe { env::remove_var("XDG_CONFIG_DIRS") }
+        }
+    }
+
+    #[test]
+    fn apple_standard_strategy_maps_app_config_under_library_preferences() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let original = env::var_os("HOME");
+        unsafe { env::set_var("HOME", "/tmp/fake-home") };
+
+        // Exercised on this (non-macOS) host via new_with_strategy, which is
+        // the whole point of AppleStandardStrategy existing independently of
+        // the host OS: it lets a Linux binary emit macOS-style layouts.
+        let sp = StandardPaths::new_with_strategy("app", "org", Strategy::AppleStandard);
+        assert_eq!(
+            sp.writable_location(AppConfigLocation),
+            Some(PathBuf::from("/tmp/fake-home/Library/Preferences/org/app"))
+        );
+
+        match original {
+            Some(value) => unsafe { env::set_var("HOME", value) },
+            None => unsafe { env::remove_var("HOME") }
+        }
+    }
+}
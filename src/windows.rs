@@ -14,7 +14,9 @@ use self::winapi::um::combaseapi::CoTaskMemFree;
 
 use ::LocationType;
 use ::LocationType::*;
-use ::StandardLocation;
+use ::StandardPaths;
+use ::Strategy;
+use ::strategy::LocationStrategy;
 
 
 /// https://msdn.microsoft.com/en-us/library/dd378457.aspx#FOLDERID_Desktop
@@ -169,26 +171,54 @@ macro_rules! sh_get_known_folder_path {
     }};
 }
 
-impl StandardLocation {
+/// Returns `true` if `path` is non-empty and absolute, the minimum bar for
+/// accepting a directory read out of an environment variable instead of
+/// `SHGetKnownFolderPath`.
+fn is_absolute_path(path: &PathBuf) -> bool {
+    path.is_absolute()
+}
+
+/// Environment-variable fallback for known folders that fail to resolve
+/// through `SHGetKnownFolderPath`, mirroring how those folders are documented
+/// to be derived in the first place.
+fn env_fallback_known_folder(location: &LocationType) -> Option<PathBuf> {
+    let var = match *location {
+        AppDataLocation => "APPDATA",
+        AppLocalDataLocation | ConfigLocation | AppConfigLocation | GenericDataLocation => "LOCALAPPDATA",
+        _ => return None
+    };
+    match env::var_os(var) {
+        Some(value) => {
+            let path = PathBuf::from(value);
+            if is_absolute_path(&path) { Some(path) } else { None }
+        },
+        None => None
+    }
+}
+
+/// [`LocationStrategy`](../strategy/trait.LocationStrategy.html) implementation
+/// backed by the Windows Known Folder API (`SHGetKnownFolderPath`).
+pub(crate) struct WindowsKnownFolderStrategy;
+
+impl LocationStrategy for WindowsKnownFolderStrategy {
 
     #[inline]
-    #[doc(hidden)]
-    pub fn writable_location_impl(&self, location: LocationType) -> Option<PathBuf> {
+    fn writable_location(&self, paths: &StandardPaths, location: LocationType) -> Option<PathBuf> {
         match location {
 
             DownloadLocation => {
                 sh_get_known_folder_path!(FOLDERID_Downloads, path, {
                     Some(path)
                 }, {
-                    self.writable_location(DocumentsLocation)
+                    self.writable_location(paths, DocumentsLocation)
                 })
             },
 
-            CacheLocation | GenericCacheLocation => {
+            GenericCacheLocation | AppCacheLocation => {
                 // FOLDERID_InternetCache points to IE's cache. Most applications seem to
                 // be using a cache directory located in their AppData directory.
-                let loc2 = if location == CacheLocation { AppLocalDataLocation } else { GenericDataLocation };
-                match self.writable_location(loc2) {
+                let loc2 = if location == AppCacheLocation { AppLocalDataLocation } else { GenericDataLocation };
+                match self.writable_location(paths, loc2) {
                     Some(mut path) => {
                         path.push("cache");
                         Some(path)
@@ -198,7 +228,7 @@ impl StandardLocation {
             },
 
             RuntimeLocation | HomeLocation =>  env::home_dir(),
-            
+
             TempLocation => Some(env::temp_dir()),
 
             _ => {
@@ -224,21 +254,29 @@ impl StandardLocation {
                 sh_get_known_folder_path!(id, mut path, {
                     if location == ConfigLocation  || location == AppConfigLocation ||
                        location == AppDataLocation || location == AppLocalDataLocation {
-                        self.append_organization_and_app(&mut path);
+                        paths.append_organization_and_app(&mut path);
                     }
                     Some(path)
                 }, {
-                    None
+                    // SHGetKnownFolderPath can fail under restricted tokens, some
+                    // service accounts, or Wine. Fall back to the environment
+                    // variables the known folders are themselves derived from.
+                    env_fallback_known_folder(&location).map(|mut path| {
+                        if location == ConfigLocation  || location == AppConfigLocation ||
+                           location == AppDataLocation || location == AppLocalDataLocation {
+                            paths.append_organization_and_app(&mut path);
+                        }
+                        path
+                    })
                 })
             }
         }
     }
-    
+
     #[inline]
-    #[doc(hidden)]
-    pub fn standard_locations_impl(&self, location: LocationType) -> Option<Vec<PathBuf>> {
+    fn standard_locations(&self, paths: &StandardPaths, location: LocationType) -> Option<Vec<PathBuf>> {
         let mut dirs = Vec::new();
-        match self.writable_location(location.clone()) {
+        match self.writable_location(paths, location.clone()) {
             Some(path) => dirs.push(path),
             _ => ()
         }
@@ -247,7 +285,7 @@ impl StandardLocation {
            location == GenericConfigLocation || location == GenericDataLocation {
             sh_get_known_folder_path!(FOLDERID_ProgramData, mut path, {
                 if location != GenericConfigLocation && location != GenericDataLocation {
-                    self.append_organization_and_app(&mut path);
+                    paths.append_organization_and_app(&mut path);
                 }
                 dirs.push(path);
             }, {});
@@ -269,3 +307,15 @@ impl StandardLocation {
         Some(dirs)
     }
 }
+
+/// Looks up the [`LocationStrategy`](../strategy/trait.LocationStrategy.html)
+/// implementation for `kind` on this platform, falling back to
+/// [`UnsupportedStrategy`](../strategy/struct.UnsupportedStrategy.html) for
+/// strategies Windows has no implementation for.
+#[doc(hidden)]
+pub fn strategy_for(kind: Strategy) -> Box<dyn LocationStrategy> {
+    match kind {
+        Strategy::WindowsKnownFolder => Box::new(WindowsKnownFolderStrategy),
+        _ => Box::new(::strategy::UnsupportedStrategy)
+    }
+}